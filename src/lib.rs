@@ -2,7 +2,7 @@
 
 #![doc(html_root_url = "https://docs.rs/mincore-rs/0.1.0")]
 
-use rustix::fs::{fstat, FileType};
+use rustix::fs::{fadvise, fstat, Advice, FileType};
 use rustix::mm::{mmap, ProtFlags, MapFlags, munmap};
 use rustix::io::{Result as RustixResult, Errno};
 
@@ -10,9 +10,158 @@ pub use rustix::param::page_size;
 
 use libc::mincore;
 
+use std::ffi::c_void;
 use std::os::fd::AsFd;
 use std::io::Error;
 
+/// Query RAM residency for an arbitrary `[addr, addr+len)` range that the
+/// caller already owns.
+///
+/// This is the low-level building block that [`mincore_wrapper`] is built on:
+/// it does not map anything itself, so callers can inspect a mapping they
+/// already hold (e.g. their own `mmap`'d region or an anonymous mapping)
+/// without the crate's internal mmap/munmap dance. The returned vector has one
+/// `bool` per page, `true` when the page is resident in RAM.
+///
+/// `addr` must be page-aligned (`mincore` returns EINVAL otherwise); this is
+/// checked before the syscall is issued.
+///
+/// # Safety
+///
+/// `addr` must point to a mapping of at least `len` bytes that stays mapped for
+/// the duration of the call.
+pub unsafe fn mincore_region(addr: *const c_void, len: usize) -> RustixResult<Vec<bool>> {
+    let page_size = page_size();
+    // mincore requires a page-aligned start address, returning EINVAL otherwise
+    if !(addr as usize).is_multiple_of(page_size) {
+        return Err(Errno::INVAL);
+    }
+    // Size is from mincore man page
+    let vec_len = len.div_ceil(page_size);
+    let mut vec_out: Vec<u8> = Vec::with_capacity(vec_len);
+
+    // SAFETY: mincore takes a pointer to a virtual memory region and writes
+    // RAM residency information to the memory region at vec_out, with the
+    // length computed above using the expression from the mincore man page
+    // We have allocated the underlying buffer by using with_capacity
+    if mincore(addr.cast_mut(), len, vec_out.as_mut_ptr()) != 0 {
+        // Returncode of either 0 (success) or -1 (failure, see errno)
+        // We don't do any other calls in between mincore and last_os_error so errno is untouched
+        // errno is thread-unique so there are no race conditions
+        return Err(Errno::from_io_error(&Error::last_os_error()).unwrap());
+    }
+    // SAFETY: we just filled up the vector with valid values
+    vec_out.set_len(vec_len);
+    // Per the mincore man page only the LSB is defined (1 = resident); the
+    // remaining bits are reserved, so mask the LSB rather than testing != 0
+    Ok(vec_out.into_iter().map(|x| x & 1 != 0).collect())
+}
+
+/// Hint the kernel to prefetch the whole file into the page cache, warming it
+/// so that a subsequent workload (or [`mincore_wrapper`] check) sees the pages
+/// resident.
+///
+/// This is a `POSIX_FADV_WILLNEED` advisory over the entire file; the kernel is
+/// free to ignore it or to bring in only some of the pages via readahead.
+pub fn advise_willneed<Fd: AsFd>(fd: &Fd) -> RustixResult<()> {
+    // offset 0, len 0 covers the file from the start to its end
+    fadvise(fd, 0, 0, Advice::WillNeed)
+}
+
+/// Hint the kernel to drop the whole file from the page cache, evicting its
+/// pages so a later [`mincore_wrapper`] check reports them non-resident.
+///
+/// This is a `POSIX_FADV_DONTNEED` advisory over the entire file. Note that
+/// `DONTNEED` on a shared mapping may not evict dirty pages, which must be
+/// written back before they can be dropped.
+pub fn advise_dontneed<Fd: AsFd>(fd: &Fd) -> RustixResult<()> {
+    // offset 0, len 0 covers the file from the start to its end
+    fadvise(fd, 0, 0, Advice::DontNeed)
+}
+
+/// A compact, bit-packed summary of per-page RAM residency.
+///
+/// One `bool` per page (as returned by [`mincore_wrapper`]) costs a byte per
+/// page, which is mostly wasted when a single bit would do; a 8 GiB file needs
+/// a 2 MB `Vec<bool>` but only 256 KiB of bits. `ResidencyMap` packs the LSBs
+/// the syscall writes into a `Vec<u64>` and offers cheap residency queries plus
+/// a [`runs`](ResidencyMap::runs) iterator over contiguous resident /
+/// non-resident spans.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResidencyMap {
+    bits: Vec<u64>,
+    page_count: usize,
+}
+
+impl ResidencyMap {
+    /// Pack a slice of per-page residency flags into the compact form.
+    fn from_pages(pages: &[bool]) -> Self {
+        let page_count = pages.len();
+        let mut bits = vec![0u64; page_count.div_ceil(64)];
+        for (idx, &resident) in pages.iter().enumerate() {
+            if resident {
+                bits[idx/64] |= 1 << (idx%64);
+            }
+        }
+        ResidencyMap { bits, page_count }
+    }
+
+    /// Whether the page at `page_idx` is resident in RAM. Pages outside the
+    /// mapped range (`page_idx >= len()`) are reported as not resident.
+    pub fn is_resident(&self, page_idx: usize) -> bool {
+        if page_idx >= self.page_count {
+            return false;
+        }
+        self.bits[page_idx/64] & (1 << (page_idx%64)) != 0
+    }
+
+    /// The number of resident pages.
+    pub fn resident_count(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// The number of pages covered by this map.
+    pub fn len(&self) -> usize {
+        self.page_count
+    }
+
+    /// Whether this map covers no pages.
+    pub fn is_empty(&self) -> bool {
+        self.page_count == 0
+    }
+
+    /// Iterate over contiguous runs of pages sharing the same residency state,
+    /// yielding `(start_page, run_len, resident)` for each run. Lets callers
+    /// report resident / non-resident spans without scanning page by page.
+    pub fn runs(&self) -> Runs<'_> {
+        Runs { map: self, page: 0 }
+    }
+}
+
+/// Iterator over contiguous residency runs of a [`ResidencyMap`], created by
+/// [`ResidencyMap::runs`].
+#[derive(Debug)]
+pub struct Runs<'a> {
+    map: &'a ResidencyMap,
+    page: usize,
+}
+
+impl Iterator for Runs<'_> {
+    type Item = (usize, usize, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.page >= self.map.page_count {
+            return None;
+        }
+        let start = self.page;
+        let resident = self.map.is_resident(start);
+        while self.page < self.map.page_count && self.map.is_resident(self.page) == resident {
+            self.page += 1;
+        }
+        Some((start, self.page-start, resident))
+    }
+}
+
 /// A function that takes a file descriptor and returns a vector indicating
 /// which pages are in memory.
 ///
@@ -20,6 +169,33 @@ use std::io::Error;
 /// caller's responsibility to ensure that `fd` refers to a regular file.
 /// (Failing to check this will result in a return value of EACCES).
 pub fn mincore_wrapper<Fd: AsFd>(fd: &Fd) -> RustixResult<Vec<bool>> {
+    let file_stat = fstat(fd)?;
+    // Micro-optimization: check if regular file first before calling mmap
+    // If it is not a regular file, return the same errno that mmap would
+    if FileType::from_raw_mode(file_stat.st_mode) != FileType::RegularFile {
+        return Err(Errno::ACCESS);
+    }
+    let file_size = usize::try_from(file_stat.st_size).unwrap();
+    mincore_wrapper_range(fd, 0, file_size)
+}
+
+/// Walk a file in page-aligned windows of at most `window_bytes`, mapping and
+/// unmapping each window in turn and invoking `callback` with the page offset
+/// of the window (in pages from the start of the file) and the residency slice
+/// for that window before moving on.
+///
+/// Unlike [`mincore_wrapper`], this never maps the whole file or holds the full
+/// result at once, bounding peak virtual-address usage and peak allocation
+/// regardless of file size — the natural building block for streaming
+/// residency stats over terabyte-scale files.
+///
+/// `window_bytes` is rounded down to a whole number of pages; it must be at
+/// least [`page_size`] bytes, otherwise `Errno::INVAL` is returned.
+pub fn mincore_wrapper_chunked<Fd: AsFd>(
+    fd: &Fd,
+    window_bytes: usize,
+    mut callback: impl FnMut(u64, &[bool]),
+) -> RustixResult<()> {
     let file_stat = fstat(fd)?;
     // Micro-optimization: check if regular file first before calling mmap
     // If it is not a regular file, return the same errno that mmap would
@@ -28,34 +204,70 @@ pub fn mincore_wrapper<Fd: AsFd>(fd: &Fd) -> RustixResult<Vec<bool>> {
     }
     let file_size = usize::try_from(file_stat.st_size).unwrap();
     let page_size = page_size();
-    // Size is from mincore man page
-    let vec_len = (file_size+page_size-1)/page_size;
-    let mut vec_out: Vec<u8> = Vec::with_capacity(vec_len);
+    // Each window must start on a page boundary, so its length is a whole
+    // number of pages; a window smaller than a single page cannot make progress
+    let window = (window_bytes/page_size)*page_size;
+    if window == 0 {
+        return Err(Errno::INVAL);
+    }
+
+    let mut offset = 0;
+    while offset < file_size {
+        let len = window.min(file_size-offset);
+        let residency = mincore_wrapper_range(fd, offset as u64, len)?;
+        callback((offset/page_size) as u64, &residency);
+        offset += len;
+    }
+    Ok(())
+}
+
+/// Like [`mincore_wrapper`], but returns the residency as a bit-packed
+/// [`ResidencyMap`] instead of a `Vec<bool>`.
+///
+/// This is the memory-friendly entry point for large files, where one byte per
+/// page would dwarf the one-bit-per-page the information actually needs.
+pub fn mincore_wrapper_packed<Fd: AsFd>(fd: &Fd) -> RustixResult<ResidencyMap> {
+    Ok(ResidencyMap::from_pages(&mincore_wrapper(fd)?))
+}
+
+/// Like [`mincore_wrapper`], but maps only the `[offset, offset+len)` window of
+/// the file instead of the whole thing.
+///
+/// This avoids mapping (and allocating a result for) the entire file, which is
+/// wasteful for large files and fails outright once `st_size` exceeds what a
+/// single mapping can cover. It is the natural entry point for windowed
+/// inspection of multi-gigabyte files.
+///
+/// `offset` must be a multiple of [`page_size`] (`mmap` requires this);
+/// otherwise `Errno::INVAL` is returned.
+pub fn mincore_wrapper_range<Fd: AsFd>(fd: &Fd, offset: u64, len: usize) -> RustixResult<Vec<bool>> {
+    // mmap requires a page-aligned file offset
+    if !offset.is_multiple_of(page_size() as u64) {
+        return Err(Errno::INVAL);
+    }
+    // An empty range has no pages; mincore(_, 0, _) succeeds and returns
+    // immediately, but mmap rejects a zero length with EINVAL, so short-circuit
+    // to match the syscall's documented behavior for empty ranges
+    if len == 0 {
+        return Ok(Vec::new());
+    }
 
     unsafe {
         // SAFETY: see argument comments
         let file_mmap = mmap(
             std::ptr::null_mut(), // pointer is location hint which can be NULL (no location hint)
-            file_size, // memory map should match the length of the file and returning an error if this is 0 is fine
+            len, // map only the requested window; never 0 here, as that case returned above
             ProtFlags::empty(), // we mmap to determine residency info, not to access the contents (and possibly perturb the state)
             MapFlags::SHARED, // we should see updates to this mmap
             fd, // is valid file descriptor that we received as argument
-             0 // start from the beginning of the file
+            offset // start from the requested (page-aligned) offset
         )?;
-        // SAFETY: mincore takes a pointer to a virtual memory region and writes
-        // RAM residency information to the memory region at vec_out, with the
-        // length computed above using the expression from the mincore man page
-        // We have allocated the underlying buffer by using with_capacity
-        if mincore(file_mmap, file_size, vec_out.as_mut_ptr()) != 0 {
-            // Returncode of either 0 (success) or -1 (failure, see errno)
-            // We don't do any other calls in between mincore and last_os_error so errno is untouched
-            // errno is thread-unique so there are no race conditions
-            return Err(Errno::from_io_error(&Error::last_os_error()).unwrap());
-        }
+        // SAFETY: file_mmap is a valid mapping of len bytes that we own for the
+        // duration of this call; mmap always returns a page-aligned address so
+        // the alignment check inside mincore_region passes
+        let res = mincore_region(file_mmap, len);
         // SAFETY: this is the unmodified pointer we got from mmap earlier
-        munmap(file_mmap, file_size)?;
-        // SAFETY: we just filled up the vector with valid values
-        vec_out.set_len(vec_len);
+        munmap(file_mmap, len)?;
+        res
     }
-    Ok(vec_out.into_iter().map(|x| x!=0).collect())
 }